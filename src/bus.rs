@@ -0,0 +1,102 @@
+//! Contains an event bus which dispatches many distinct event argument types
+//! through a single object, keyed by `TypeId`.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+use crate::Event;
+use crate::EmitResult;
+use crate::id::HandlerId;
+use crate::handler::EventHandler;
+use crate::priority::Priority;
+
+/// An event bus dispatches events of many different argument types through a
+/// single object. Internally, it keeps one `Event` per argument type, keyed
+/// by `TypeId`, so unrelated modules can share a single dispatcher while
+/// each argument type's handlers still run concurrently exactly as
+/// `Event::emit` would run them standalone.
+///
+/// # Example
+///
+/// ```
+/// use concurrent_event::bus::EventBus;
+/// use concurrent_event::handler::StatelessEventHandler;
+/// use concurrent_event::priority::Priority;
+///
+/// let mut bus = EventBus::new();
+/// bus.subscribe(StatelessEventHandler::new(|arg: &str| println!("{}", arg)), Priority::Normal);
+/// bus.subscribe(StatelessEventHandler::new(|arg: i32| println!("{}", arg)), Priority::Normal);
+/// bus.post("Hello, World!");
+/// bus.post(42);
+/// ```
+pub struct EventBus {
+    events: HashMap<TypeId, Box<dyn Any + Send>>
+}
+
+impl EventBus {
+
+    /// Creates a new event bus without any subscribers.
+    pub fn new() -> EventBus {
+        EventBus {
+            events: HashMap::new()
+        }
+    }
+
+    fn event_mut<A: 'static + Copy + Send>(&mut self) ->
+            &mut Event<A, Box<dyn EventHandler<A, Output = ()>>> {
+        self.events.entry(TypeId::of::<A>())
+            .or_insert_with(|| Box::new(Event::<A, Box<dyn EventHandler<A, Output = ()>>>::new()))
+            .downcast_mut()
+            .expect("TypeId bucket held an event of the wrong argument type")
+    }
+
+    /// Subscribes a handler to events of the given argument type. A handler
+    /// ID is returned, which can be used to identify the handler later.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `A`: The event argument type to subscribe to.
+    ///
+    /// # Parameters
+    ///
+    /// * `handler`: The event handler to register.
+    /// * `priority`: The priority under which the handler is run.
+    pub fn subscribe<A: 'static + Copy + Send>(&mut self,
+            handler: impl EventHandler<A, Output = ()> + 'static,
+            priority: Priority) -> HandlerId {
+        self.event_mut::<A>().add_handler(Box::new(handler), priority)
+    }
+
+    /// Posts an event argument to every handler subscribed to its type. If
+    /// no handler has ever subscribed to `A`, this is a no-op and `true` is
+    /// returned, mirroring `Event::emit` on an event without handlers.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `A`: The event argument type to post.
+    ///
+    /// # Parameters
+    ///
+    /// * `arg`: The event argument to dispatch.
+    pub fn post<A: 'static + Copy + Send>(&mut self, arg: A) -> bool {
+        match self.events.get_mut(&TypeId::of::<A>()) {
+            Some(event) => {
+                let event: &mut Event<A, Box<dyn EventHandler<A, Output = ()>>> = event
+                    .downcast_mut()
+                    .expect("TypeId bucket held an event of the wrong argument type");
+                match event.emit(arg) {
+                    EmitResult::AllRan(_) => true,
+                    EmitResult::Cancelled { panicked, .. } => !panicked,
+                    EmitResult::Panicked(_) => false
+                }
+            },
+            None => true
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> EventBus {
+        EventBus::new()
+    }
+}
@@ -0,0 +1,34 @@
+//! Contains the definition of the event context, which is passed to handlers
+//! during `Event::emit` to support cooperative cancellation.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A shared context passed to event handlers during `Event::emit`. It allows
+/// a handler to cancel the event, which causes any handler phases of lower
+/// priority than the current one to be skipped.
+#[derive(Clone)]
+pub struct EventContext {
+    cancelled: Arc<AtomicBool>
+}
+
+impl EventContext {
+
+    pub(crate) fn new() -> EventContext {
+        EventContext {
+            cancelled: Arc::new(AtomicBool::new(false))
+        }
+    }
+
+    /// Cancels the event. Any handler phase that has not yet started
+    /// executing will be skipped by `Event::emit`.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Indicates whether the event has been cancelled, i.e. whether some
+    /// handler has already called `cancel` during the current `emit`.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
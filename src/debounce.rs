@@ -0,0 +1,192 @@
+//! Contains a debounced event wrapper which coalesces bursts of emissions
+//! into a single dispatch.
+
+use std::marker::PhantomData;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::Event;
+use crate::handler::EventHandler;
+
+enum Message<A> {
+    Arg(A),
+    Flush(Sender<()>),
+    Shutdown
+}
+
+/// Wraps an `Event` to coalesce bursts of emissions into a single dispatch.
+/// Arguments pushed via `emit_debounced` are sent to a background worker
+/// thread, which keeps only the most recently received argument. Once the
+/// stream of arguments has been quiet for `window`, or `max_wait` has
+/// elapsed since the first argument of the current burst, whichever comes
+/// first, the worker dispatches the latest argument to the wrapped `Event`
+/// via `Event::emit`.
+///
+/// This is useful for high-frequency, UI/IO-style events - such as resize,
+/// scroll, or filesystem notifications - where only the final state
+/// matters and dispatching on every single emission would be wasteful.
+///
+/// # Type Parameters
+///
+/// * `A`: The type of event arguments which are distributed to the handlers.
+/// * `H`: The type of event handlers registered with the wrapped `Event`.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use concurrent_event::Event;
+/// use concurrent_event::debounce::DebouncedEvent;
+/// use concurrent_event::handler::StatelessEventHandler;
+/// use concurrent_event::priority::Priority;
+///
+/// let mut event = Event::<i32, StatelessEventHandler<i32>>::new();
+/// event.add_handler(StatelessEventHandler::new(|arg| println!("resized to {}", arg)),
+///     Priority::Normal);
+///
+/// let debounced = DebouncedEvent::new(event, Duration::from_millis(50), Duration::from_secs(1));
+/// debounced.emit_debounced(100);
+/// debounced.emit_debounced(200);
+/// debounced.flush();
+/// ```
+pub struct DebouncedEvent<A: Copy + Send, H: EventHandler<A>> {
+    sender: Sender<Message<A>>,
+    worker: Option<thread::JoinHandle<()>>,
+    handler_type: PhantomData<H>
+}
+
+impl<A, H> DebouncedEvent<A, H>
+where
+    A: Copy + Send + 'static,
+    H: EventHandler<A> + 'static,
+    H::Output: Send
+{
+    /// Wraps `event` in a `DebouncedEvent`, spawning the background worker
+    /// thread that coalesces emissions.
+    ///
+    /// # Parameters
+    ///
+    /// * `event`: The event to dispatch to once a burst settles.
+    /// * `window`: The quiet period that must pass after the latest emission
+    /// before it is dispatched.
+    /// * `max_wait`: An upper bound on how long a continuous stream of
+    /// emissions can postpone dispatch, so a never-quiet stream still fires
+    /// periodically rather than starving forever.
+    pub fn new(event: Event<A, H>, window: Duration, max_wait: Duration) -> DebouncedEvent<A, H> {
+        let (sender, receiver) = mpsc::channel();
+        let worker = thread::spawn(move || worker_loop(receiver, event, window, max_wait));
+
+        DebouncedEvent {
+            sender,
+            worker: Some(worker),
+            handler_type: PhantomData
+        }
+    }
+
+    /// Pushes an event argument to be dispatched once the current burst
+    /// settles. If further arguments arrive within `window`, only the most
+    /// recently pushed one will actually be dispatched.
+    ///
+    /// # Parameters
+    ///
+    /// * `arg`: The event argument to push.
+    pub fn emit_debounced(&self, arg: A) {
+        let _ = self.sender.send(Message::Arg(arg));
+    }
+
+    /// Dispatches any pending argument immediately, without waiting for
+    /// `window` or `max_wait` to elapse. Blocks until the dispatch, if any,
+    /// has completed. Does nothing if no argument is currently pending.
+    pub fn flush(&self) {
+        let (ack_sender, ack_receiver) = mpsc::channel();
+
+        if self.sender.send(Message::Flush(ack_sender)).is_ok() {
+            let _ = ack_receiver.recv();
+        }
+    }
+}
+
+impl<A: Copy + Send, H: EventHandler<A>> Drop for DebouncedEvent<A, H> {
+
+    /// Dispatches any pending argument and stops the background worker
+    /// thread.
+    fn drop(&mut self) {
+        let _ = self.sender.send(Message::Shutdown);
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn worker_loop<A, H>(receiver: Receiver<Message<A>>, mut event: Event<A, H>, window: Duration,
+        max_wait: Duration)
+where
+    A: Copy + Send,
+    H: EventHandler<A>,
+    H::Output: Send
+{
+    let mut pending: Option<A> = None;
+    let mut deadline: Option<Instant> = None;
+    let mut max_deadline: Option<Instant> = None;
+
+    loop {
+        let wait_until = match (deadline, max_deadline) {
+            (Some(deadline), Some(max_deadline)) => Some(deadline.min(max_deadline)),
+            (Some(deadline), None) => Some(deadline),
+            (None, Some(max_deadline)) => Some(max_deadline),
+            (None, None) => None
+        };
+
+        let message = match wait_until {
+            Some(deadline) => {
+                match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(message) => Some(message),
+                    Err(mpsc::RecvTimeoutError::Timeout) => None,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break
+                }
+            },
+            None => {
+                match receiver.recv() {
+                    Ok(message) => Some(message),
+                    Err(_) => break
+                }
+            }
+        };
+
+        match message {
+            Some(Message::Arg(arg)) => {
+                let now = Instant::now();
+                pending = Some(arg);
+                deadline = Some(now + window);
+                max_deadline.get_or_insert(now + max_wait);
+            },
+            Some(Message::Flush(ack)) => {
+                if let Some(arg) = pending.take() {
+                    event.emit(arg);
+                }
+
+                deadline = None;
+                max_deadline = None;
+                let _ = ack.send(());
+            },
+            Some(Message::Shutdown) => {
+                if let Some(arg) = pending.take() {
+                    event.emit(arg);
+                }
+
+                break;
+            },
+            None => {
+                if let Some(arg) = pending.take() {
+                    event.emit(arg);
+                }
+
+                deadline = None;
+                max_deadline = None;
+            }
+        }
+    }
+}
@@ -1,89 +1,122 @@
 //! Contains the definition of the event handler trait as well as some standard
 //! implementations for common use cases.
 
+use crate::context::EventContext;
+
 /// A trait for event handlers which can be registered with an event. For
-/// comfort, an implementation for `Box<dyn EventHandler<A>>` is provided.
+/// comfort, an implementation for `Box<dyn EventHandler<A, Output = R>>` is
+/// provided.
 ///
 /// # Type Parameters
 ///
 /// * `A`: The type of event arguments accepted by this handler.
 pub trait EventHandler<A> : Send {
-    fn on_event(&mut self, arg: A);
+
+    /// The type of value produced by this handler whenever it handles an
+    /// event. Use `()` for handlers which do not need to report a result;
+    /// `Event::emit` then collects these into a map keyed by handler ID.
+    type Output;
+
+    fn on_event(&mut self, arg: A) -> Self::Output;
+
+    /// Like `on_event`, but additionally receives the `EventContext` shared
+    /// by all handlers of the current `Event::emit` call, which allows the
+    /// handler to cooperatively cancel any handler phases of lower priority.
+    /// The default implementation ignores the context and simply delegates
+    /// to `on_event`.
+    ///
+    /// # Parameters
+    ///
+    /// * `arg`: The event argument to handle.
+    /// * `ctx`: The context of the current `emit` call.
+    fn on_event_ctx(&mut self, arg: A, ctx: &EventContext) -> Self::Output {
+        let _ = ctx;
+        self.on_event(arg)
+    }
 }
 
 /// An event handler which manages a simple closure that receives no state
 /// except the event argument. The closure is executed every time an event is
-/// received.
+/// received, and its return value is reported as the handler's output.
 ///
 /// # Type Parameters
 ///
 /// * `A`: The type of event arguments accepted by this handler.
+/// * `R`: The type of value produced by the closure for each event. Defaults
+/// to `()` for handlers that do not need to report a result.
 ///
 /// # Example
 ///
 /// ```
 /// use concurrent_event::Event;
 /// use concurrent_event::handler::StatelessEventHandler;
+/// use concurrent_event::priority::Priority;
 ///
 /// let mut ev = Event::<&str, StatelessEventHandler<&str>>::new();
 /// let handler = StatelessEventHandler::new(|arg: &str| println!("{}", arg));
-/// ev.add_handler(handler);
+/// ev.add_handler(handler, Priority::Normal);
 /// ev.emit("Hello World!");
 /// ```
-pub struct StatelessEventHandler<'a, A> {
-    func: Box<dyn Fn(A) + Send + 'a>
+pub struct StatelessEventHandler<'a, A, R = ()> {
+    func: Box<dyn Fn(A) -> R + Send + 'a>
 }
 
-impl<'a, A> StatelessEventHandler<'a, A> {
+impl<'a, A, R> StatelessEventHandler<'a, A, R> {
     /// Creates a new stateless event handler from a closure.
     ///
     /// # Parameters
     ///
     /// * `f`: A closure which is executed every time an event is received. It
-    /// consumes the event argument.
-    pub fn new(f: impl Fn(A) + Send + 'a) -> StatelessEventHandler<'a, A> {
+    /// consumes the event argument and produces the handler's output.
+    pub fn new(f: impl Fn(A) -> R + Send + 'a) -> StatelessEventHandler<'a, A, R> {
         StatelessEventHandler {
             func: Box::new(f)
         }
     }
 }
 
-impl<'a, A> EventHandler<A> for StatelessEventHandler<'a, A> {
-    fn on_event(&mut self, arg: A) {
+impl<'a, A, R> EventHandler<A> for StatelessEventHandler<'a, A, R> {
+    type Output = R;
+
+    fn on_event(&mut self, arg: A) -> R {
         (self.func)(arg)
     }
 }
 
 /// An event handler which manages a closure together with some state which can
 /// track information over multiple events. The closure is executed with a
-/// mutable reference of the state every time an event is received.
+/// mutable reference of the state every time an event is received, and its
+/// return value is reported as the handler's output.
 ///
 /// # Type Parameters
 ///
 /// * `A`: The type of event arguments accepted by this handler.
 /// * `S`: The type of the state maintained by this handler.
+/// * `R`: The type of value produced by the closure for each event. Defaults
+/// to `()` for handlers that do not need to report a result.
 ///
 /// # Example
 ///
 /// ```
 /// use concurrent_event::Event;
 /// use concurrent_event::handler::StatefulEventHandler;
+/// use concurrent_event::priority::Priority;
 ///
 /// let mut ev = Event::<i32, StatefulEventHandler<i32, i32>>::new();
 /// let handler = StatefulEventHandler::new(|arg: i32, state: &mut i32| *state += arg, 0);
-/// let id = ev.add_handler(handler);
+/// let id = ev.add_handler(handler, Priority::Normal);
 /// ev.emit(2);
 /// ev.emit(3);
 /// let state = *ev.get_handler(id).unwrap().state();
-/// 
+///
 /// assert_eq!(5, state);
 /// ```
-pub struct StatefulEventHandler<'a, A, S: Send> {
-    func: Box<dyn Fn(A, &mut S) + Send + 'a>,
+pub struct StatefulEventHandler<'a, A, S: Send, R = ()> {
+    func: Box<dyn Fn(A, &mut S) -> R + Send + 'a>,
     state: S
 }
 
-impl<'a, A, S: Send> StatefulEventHandler<'a, A, S> {
+impl<'a, A, S: Send, R> StatefulEventHandler<'a, A, S, R> {
 
     /// Creates a new stateful event handler from a closure and the initial
     /// state.
@@ -92,12 +125,12 @@ impl<'a, A, S: Send> StatefulEventHandler<'a, A, S> {
     ///
     /// * `f`: A closure which is executed every time an event is received. It
     /// consumes the event argument and gets a mutable reference to the current
-    /// state.
+    /// state, and produces the handler's output.
     /// * `initial_state`: The initial state given to the closure in the first
     /// received event.
-    pub fn new<F>(f: F, initial_state: S) -> StatefulEventHandler<'a, A, S>
+    pub fn new<F>(f: F, initial_state: S) -> StatefulEventHandler<'a, A, S, R>
     where
-        F : Fn(A, &mut S) + Send + 'a
+        F : Fn(A, &mut S) -> R + Send + 'a
     {
         StatefulEventHandler {
             func: Box::new(f),
@@ -111,14 +144,22 @@ impl<'a, A, S: Send> StatefulEventHandler<'a, A, S> {
     }
 }
 
-impl<'a, A, S: Send> EventHandler<A> for StatefulEventHandler<'a, A, S> {
-    fn on_event(&mut self, arg: A) {
+impl<'a, A, S: Send, R> EventHandler<A> for StatefulEventHandler<'a, A, S, R> {
+    type Output = R;
+
+    fn on_event(&mut self, arg: A) -> R {
         (self.func)(arg, &mut self.state)
     }
 }
 
-impl<'a, A> EventHandler<A> for Box<dyn EventHandler<A> + 'a> {
-    fn on_event(&mut self, arg: A) {
+impl<'a, A, R> EventHandler<A> for Box<dyn EventHandler<A, Output = R> + 'a> {
+    type Output = R;
+
+    fn on_event(&mut self, arg: A) -> R {
         self.as_mut().on_event(arg)
     }
+
+    fn on_event_ctx(&mut self, arg: A, ctx: &EventContext) -> R {
+        self.as_mut().on_event_ctx(arg, ctx)
+    }
 }
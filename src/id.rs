@@ -10,7 +10,7 @@ pub const HANDLER_ID_BYTES: usize = 32;
 
 /// An ID for an event handler that consists of random bytes. Uniqueness
 /// depends on sufficient randomness in generation.
-#[derive(PartialEq, Eq, Hash, Copy, Clone)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub struct HandlerId {
     bytes: [u8; HANDLER_ID_BYTES]
 }
@@ -19,33 +19,136 @@
 //!
 //! ```
 //! use concurrent_event::Event;
-//! use concurrent_event::handler::EventHandler; 
+//! use concurrent_event::handler::EventHandler;
+//! use concurrent_event::priority::Priority;
 //!
 //! struct Printer;
 //!
 //! impl EventHandler<&str> for Printer {
+//!     type Output = ();
+//!
 //!     fn on_event(&mut self, arg: &str) {
 //!         print!("{}", arg);
 //!     }
 //! }
 //!
 //! let mut event = Event::<&str, Printer>::new();
+//! event.add_handler(Printer, Priority::Normal);
 //! event.emit("Hello, World!");
 //! ```
 //!
 //! In the `handler` package, default implementation for stateless and stateful
 //! event handlers can be found, which take a closure at construction.
+//!
+//! Handlers are assigned a `Priority` on registration. `emit` groups handlers
+//! into phases by descending priority and joins each phase before starting
+//! the next, so a `Highest`-priority handler is guaranteed to have finished
+//! before any `High`-priority handler starts, and so on. Within a phase,
+//! handlers still run concurrently exactly as before. A handler may
+//! cooperatively cancel the event via the `EventContext` passed to
+//! `EventHandler::on_event_ctx`, which causes any remaining, lower-priority
+//! phases to be skipped.
+//!
+//! Handlers may also report a value for every event they handle via their
+//! `EventHandler::Output` associated type. `emit` collects these into a map
+//! keyed by `HandlerId`, which allows an event to be used as a parallel
+//! fan-out/fan-in, e.g. to poll all subscribers for votes or computed
+//! contributions.
+//!
+//! The `bus` module contains `EventBus`, which dispatches many distinct
+//! argument types through a single object instead of requiring one `Event`
+//! per type. The `debounce` module contains `DebouncedEvent`, which
+//! coalesces bursts of emissions of a single event into one dispatch. The
+//! `synth` module contains `EventSynthesizer`, which lets a newly
+//! registered handler catch up on current state via `add_handler_synth`.
+//! `emit_with_timeout` signals a cooperative deadline to handlers via a
+//! watchdog thread and reports, in the `timeout` module's
+//! `TimeoutEmitResult`, which handlers were still running when it passed;
+//! it still waits for handlers that do not bail out. The `subscription`
+//! module contains `Subscription`, an RAII guard returned by
+//! `add_handler_scoped` that removes its handler on `Drop`.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
 
 use crossbeam::thread;
 
+use crate::context::EventContext;
 use crate::id::HandlerId;
 use crate::handler::EventHandler;
+use crate::priority::Priority;
+use crate::subscription::Subscription;
+use crate::synth::EventSynthesizer;
+use crate::timeout::TimeoutEmitResult;
 
+pub mod bus;
+pub mod context;
+pub mod debounce;
 pub mod id;
 pub mod handler;
+pub mod priority;
+pub mod subscription;
+pub mod synth;
+pub mod timeout;
+
+/// The result of an `Event::emit` call, distinguishing the different ways in
+/// which dispatch may conclude. Each variant carries the outputs reported by
+/// the handlers that did run, keyed by their `HandlerId`.
+///
+/// # Type Parameters
+///
+/// * `R`: The type of value reported by each handler, i.e. `H::Output` for
+/// the `Event<A, H>` that produced this result.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum EmitResult<R> {
+
+    /// All handler phases ran to completion without being cancelled.
+    AllRan(HashMap<HandlerId, R>),
+
+    /// A handler cancelled the event via its `EventContext`, so any
+    /// lower-priority phases that had not started yet were skipped.
+    Cancelled {
+
+        /// The outputs reported by the handlers that ran.
+        results: HashMap<HandlerId, R>,
+
+        /// Whether a handler in the same or an earlier phase also panicked.
+        /// Cancellation takes precedence over this variant's counterpart
+        /// `Panicked`, so this flag is the only way to observe that both
+        /// happened during the same `emit`.
+        panicked: bool
+    },
+
+    /// At least one handler panicked during dispatch.
+    Panicked(HashMap<HandlerId, R>)
+}
+
+impl<R> EmitResult<R> {
+
+    /// Gets a reference to the outputs reported by the handlers that ran,
+    /// keyed by their `HandlerId`, regardless of which variant this result
+    /// is.
+    pub fn results(&self) -> &HashMap<HandlerId, R> {
+        match self {
+            EmitResult::AllRan(results) => results,
+            EmitResult::Cancelled { results, .. } => results,
+            EmitResult::Panicked(results) => results
+        }
+    }
+
+    /// Consumes this result and returns the outputs reported by the handlers
+    /// that ran, keyed by their `HandlerId`, regardless of which variant
+    /// this result is.
+    pub fn into_results(self) -> HashMap<HandlerId, R> {
+        match self {
+            EmitResult::AllRan(results) => results,
+            EmitResult::Cancelled { results, .. } => results,
+            EmitResult::Panicked(results) => results
+        }
+    }
+}
 
 /// An event manages multiple handlers which can be registered.
 ///
@@ -53,10 +156,10 @@ pub mod handler;
 ///
 /// * `A`: The type of event arguments which are distributed to the handlers.
 /// * `H`: The type of event handlers which can be registered with this event.
-/// To allow for different types, use `Box<dyn EventHandler<...>>`.
+/// To allow for different types, use `Box<dyn EventHandler<A, Output = R>>`.
 pub struct Event<A: Copy + Send, H: EventHandler<A>> {
     arg_type: PhantomData<A>,
-    handlers: HashMap<HandlerId, H>
+    handlers: HashMap<HandlerId, (Priority, H)>
 }
 
 impl<A: Copy + Send, H: EventHandler<A>> Event<A, H> {
@@ -69,19 +172,209 @@ impl<A: Copy + Send, H: EventHandler<A>> Event<A, H> {
         }
     }
 
-    /// Emits an event, invoking all currently registered handlers in parallel.
-    /// If all event handlers terminated without panicking, `true` is returned.
-    /// If any event handler panics, `false` is returned.
+    /// Emits an event, invoking all currently registered handlers grouped
+    /// into phases by descending priority. Handlers within the same phase
+    /// are run concurrently as before, but each phase is joined before the
+    /// next one starts. If any handler cancels the event via its
+    /// `EventContext`, subsequent, lower-priority phases are skipped. The
+    /// outputs reported by the handlers that ran are returned keyed by
+    /// their `HandlerId`.
+    ///
+    /// # Parameters
+    ///
+    /// * `arg`: The event argument to dispatch.
+    pub fn emit(&mut self, arg: A) -> EmitResult<H::Output>
+    where
+        H::Output: Send
+    {
+        let ctx = EventContext::new();
+        let mut priorities: Vec<Priority> =
+            self.handlers.values().map(|(priority, _)| *priority).collect();
+        priorities.sort();
+        priorities.dedup();
+
+        let mut results = HashMap::new();
+        let mut panicked = false;
+
+        for priority in priorities.into_iter().rev() {
+            if ctx.is_cancelled() {
+                return EmitResult::Cancelled { results, panicked };
+            }
+
+            let (phase_results, phase_panicked) = thread::scope(|s| {
+                let handles: Vec<(HandlerId, _)> = self.handlers.iter_mut()
+                    .filter(|(_, (phase_priority, _))| *phase_priority == priority)
+                    .map(|(&id, (_, handler))| {
+                        let ctx = &ctx;
+                        (id, s.spawn(move |_| handler.on_event_ctx(arg, ctx)))
+                    })
+                    .collect();
+
+                let mut phase_results = HashMap::new();
+                let mut phase_panicked = false;
+
+                for (id, handle) in handles {
+                    match handle.join() {
+                        Ok(result) => { phase_results.insert(id, result); },
+                        Err(_) => phase_panicked = true
+                    }
+                }
+
+                (phase_results, phase_panicked)
+            }).expect("handlers are always joined explicitly, so this cannot panic");
+
+            results.extend(phase_results);
+            panicked = panicked || phase_panicked;
+        }
+
+        if ctx.is_cancelled() {
+            EmitResult::Cancelled { results, panicked }
+        }
+        else if panicked {
+            EmitResult::Panicked(results)
+        }
+        else {
+            EmitResult::AllRan(results)
+        }
+    }
+
+    /// Emits an event like `emit`, but folds the outputs reported by the
+    /// handlers that ran into a single value as each phase joins, instead of
+    /// collecting them into a map keyed by `HandlerId`. This avoids paying
+    /// for that map when only an aggregate is needed, e.g. summing computed
+    /// contributions or combining votes. Like `emit`, phases are run in
+    /// descending priority order and a handler cancelling the event via its
+    /// `EventContext` skips any remaining, lower-priority phases; a handler
+    /// that panics simply does not contribute to the fold.
+    ///
+    /// # Parameters
+    ///
+    /// * `arg`: The event argument to dispatch.
+    /// * `init`: The initial value of the accumulator.
+    /// * `fold`: A function combining the accumulator with each handler's
+    /// output. Handlers are folded in an unspecified order.
+    pub fn emit_reduce<B>(&mut self, arg: A, init: B, fold: impl Fn(B, H::Output) -> B) -> B
+    where
+        H::Output: Send
+    {
+        let ctx = EventContext::new();
+        let mut priorities: Vec<Priority> =
+            self.handlers.values().map(|(priority, _)| *priority).collect();
+        priorities.sort();
+        priorities.dedup();
+
+        let mut acc = init;
+
+        for priority in priorities.into_iter().rev() {
+            if ctx.is_cancelled() {
+                break;
+            }
+
+            acc = thread::scope(|s| {
+                let handles: Vec<_> = self.handlers.iter_mut()
+                    .filter(|(_, (phase_priority, _))| *phase_priority == priority)
+                    .map(|(_, (_, handler))| {
+                        let ctx = &ctx;
+                        s.spawn(move |_| handler.on_event_ctx(arg, ctx))
+                    })
+                    .collect();
+
+                let mut acc = acc;
+
+                for handle in handles {
+                    if let Ok(output) = handle.join() {
+                        acc = fold(acc, output);
+                    }
+                }
+
+                acc
+            }).expect("handlers are always joined explicitly, so this cannot panic");
+        }
+
+        acc
+    }
+
+    /// Emits an event with a cooperative timeout, ignoring priority phases
+    /// and instead running every currently registered handler concurrently
+    /// in one go, as the original, pre-priority `emit` did. A watchdog
+    /// thread waits on a condition variable that the main thread notifies as
+    /// soon as every handler has joined, so a fast dispatch returns as soon
+    /// as it is done. If that wait times out instead, the watchdog signals
+    /// the shared `EventContext`, so well-behaved handlers that poll it via
+    /// `on_event_ctx` can bail out early. Since threads cannot be preempted,
+    /// a handler that never polls the context is still waited for; the
+    /// returned result merely reports which handlers had completed by the
+    /// deadline, which were still running, and which panicked.
     ///
     /// # Parameters
     ///
     /// * `arg`: The event argument to dispatch.
-    pub fn emit(&mut self, arg: A) -> bool {
+    /// * `timeout`: The duration after which the shared `EventContext` is
+    /// signalled so handlers can cooperatively bail out.
+    pub fn emit_with_timeout(&mut self, arg: A, timeout: Duration) -> TimeoutEmitResult {
+        let ctx = EventContext::new();
+        let completed: Mutex<HashSet<HandlerId>> = Mutex::new(HashSet::new());
+        let at_deadline: Mutex<Option<HashSet<HandlerId>>> = Mutex::new(None);
+        let all_joined = Arc::new((Mutex::new(false), Condvar::new()));
+        let mut panicked = HashSet::new();
+
         thread::scope(|s| {
-            for handler in self.handlers.values_mut() {
-                s.spawn(move |_| handler.on_event(arg));
+            let handles: Vec<(HandlerId, _)> = self.handlers.iter_mut()
+                .map(|(&id, (_, handler))| {
+                    let ctx = ctx.clone();
+                    let completed = &completed;
+                    (id, s.spawn(move |_| {
+                        let result = std::panic::catch_unwind(
+                            std::panic::AssertUnwindSafe(|| handler.on_event_ctx(arg, &ctx)));
+                        completed.lock().unwrap().insert(id);
+
+                        if let Err(payload) = result {
+                            std::panic::resume_unwind(payload);
+                        }
+                    }))
+                })
+                .collect();
+
+            let watchdog_ctx = ctx.clone();
+            let completed_ref = &completed;
+            let at_deadline_ref = &at_deadline;
+            let watchdog_all_joined = Arc::clone(&all_joined);
+            s.spawn(move |_| {
+                let (joined, condvar) = &*watchdog_all_joined;
+                let joined = joined.lock().unwrap();
+                let (_joined, wait_result) =
+                    condvar.wait_timeout_while(joined, timeout, |&mut joined| !joined).unwrap();
+
+                if wait_result.timed_out() {
+                    watchdog_ctx.cancel();
+                    let snapshot = completed_ref.lock().unwrap().clone();
+                    *at_deadline_ref.lock().unwrap() = Some(snapshot);
+                }
+            });
+
+            for (id, handle) in handles {
+                if handle.join().is_err() {
+                    panicked.insert(id);
+                }
             }
-        }).is_ok()
+
+            let (joined, condvar) = &*all_joined;
+            *joined.lock().unwrap() = true;
+            condvar.notify_all();
+        }).expect("handlers are always joined explicitly, so this cannot panic");
+
+        let completed = at_deadline.into_inner().unwrap()
+            .unwrap_or_else(|| completed.into_inner().unwrap());
+        let timed_out = self.handlers.keys()
+            .filter(|id| !completed.contains(*id))
+            .copied()
+            .collect();
+
+        TimeoutEmitResult {
+            completed,
+            timed_out,
+            panicked
+        }
     }
 
     /// Adds an event handler to notify for future events. A handler ID is
@@ -90,12 +383,39 @@ impl<A: Copy + Send, H: EventHandler<A>> Event<A, H> {
     /// # Parameters
     ///
     /// * `handler`: The event handler to register.
-    pub fn add_handler(&mut self, handler: H) -> HandlerId {
+    /// * `priority`: The priority under which the handler is run. Handlers
+    /// of higher priority are guaranteed to complete their phase before any
+    /// handler of lower priority starts in `emit` and `emit_reduce`;
+    /// `emit_with_timeout` runs all handlers concurrently regardless of
+    /// priority.
+    pub fn add_handler(&mut self, handler: H, priority: Priority) -> HandlerId {
         let id = HandlerId::new();
-        self.handlers.insert(id, handler);
+        self.handlers.insert(id, (priority, handler));
         id
     }
 
+    /// Adds an event handler like `add_handler`, but first lets it catch up
+    /// on the current state. `synth.synthesize()` is run once and each
+    /// produced argument is delivered to `handler` alone via `on_event`,
+    /// before it joins the other handlers. This lets a handler which models
+    /// accumulated state, such as a counter or cache, initialize correctly
+    /// when it subscribes mid-stream.
+    ///
+    /// # Parameters
+    ///
+    /// * `handler`: The event handler to register.
+    /// * `priority`: The priority under which the handler is run.
+    /// * `synth`: The synthesizer producing the catch-up batch delivered to
+    /// `handler` before registration.
+    pub fn add_handler_synth(&mut self, mut handler: H, priority: Priority,
+            synth: &impl EventSynthesizer<A>) -> HandlerId {
+        for arg in synth.synthesize() {
+            handler.on_event(arg);
+        }
+
+        self.add_handler(handler, priority)
+    }
+
     /// Gets a reference to the event handler registered under the given ID
     /// wrapped in a `Some` option variant. If no such handler is registered,
     /// `None` is returned.
@@ -104,21 +424,62 @@ impl<A: Copy + Send, H: EventHandler<A>> Event<A, H> {
     ///
     /// * `id`: The handler ID for which to get the associated event handler.
     pub fn get_handler(&self, id: HandlerId) -> Option<&H> {
-        self.handlers.get(&id)
+        self.handlers.get(&id).map(|(_, handler)| handler)
+    }
+
+    /// Gets a mutable reference to the event handler registered under the
+    /// given ID wrapped in a `Some` option variant. If no such handler is
+    /// registered, `None` is returned.
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: The handler ID for which to get the associated event handler.
+    pub fn get_handler_mut(&mut self, id: HandlerId) -> Option<&mut H> {
+        self.handlers.get_mut(&id).map(|(_, handler)| handler)
+    }
+
+    /// Removes the event handler registered under the given ID, if any, and
+    /// returns it wrapped in a `Some` option variant. If no such handler is
+    /// registered, `None` is returned.
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: The handler ID of the handler to remove.
+    pub fn remove_handler(&mut self, id: HandlerId) -> Option<H> {
+        self.handlers.remove(&id).map(|(_, handler)| handler)
+    }
+
+    /// Adds an event handler like `add_handler`, but returns an RAII
+    /// `Subscription` guard instead of a bare `HandlerId`. The handler is
+    /// removed automatically when the guard is dropped, which makes
+    /// dynamic, lifetime-bound subscriptions - temporary observers, test
+    /// fixtures - ergonomic instead of leaking handlers for the lifetime of
+    /// the event.
+    ///
+    /// # Parameters
+    ///
+    /// * `handler`: The event handler to register.
+    /// * `priority`: The priority under which the handler is run.
+    pub fn add_handler_scoped(&mut self, handler: H, priority: Priority) ->
+            Subscription<'_, A, H> {
+        let id = self.add_handler(handler, priority);
+        Subscription { event: self, id }
     }
 }
 
-impl<'a, A: Copy + Send> Event<A, Box<dyn EventHandler<A> + 'a>> {
+impl<'a, A: Copy + Send, R> Event<A, Box<dyn EventHandler<A, Output = R> + 'a>> {
 
     /// Adds an event handler wrapped into a box to this event. This is mainly
-    /// syntactic sugar for `event.add_handler(Box::new(handler))`.
+    /// syntactic sugar for `event.add_handler(Box::new(handler), priority)`.
     ///
     /// # Parameters
     ///
     /// * `handler`: The event handler to wrap in a box and register with this
     /// event.
-    pub fn add_handler_boxed(&'a mut self, handler: impl EventHandler<A> + 'a) -> HandlerId {
-        self.add_handler(Box::new(handler))
+    /// * `priority`: The priority under which the handler is run.
+    pub fn add_handler_boxed(&'a mut self, handler: impl EventHandler<A, Output = R> + 'a,
+            priority: Priority) -> HandlerId {
+        self.add_handler(Box::new(handler), priority)
     }
 }
 
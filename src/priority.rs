@@ -0,0 +1,22 @@
+//! Contains the definition of handler priorities, which control the order in
+//! which handler phases are executed by `Event::emit`.
+
+/// A priority assigned to an event handler, controlling when it is invoked
+/// relative to other handlers registered with the same event. Handlers are
+/// grouped into phases by priority and `Event::emit` runs the `Highest`
+/// phase first, then `High`, and so on down to `Lowest`. Handlers within the
+/// same phase still run concurrently, as before.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+pub enum Priority {
+    Lowest,
+    Low,
+    Normal,
+    High,
+    Highest
+}
+
+impl Default for Priority {
+    fn default() -> Priority {
+        Priority::Normal
+    }
+}
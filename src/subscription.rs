@@ -0,0 +1,49 @@
+//! Contains the `Subscription` RAII guard returned by
+//! `Event::add_handler_scoped`.
+
+use crate::Event;
+use crate::handler::EventHandler;
+use crate::id::HandlerId;
+
+/// An RAII guard representing a handler registered via
+/// `Event::add_handler_scoped`. While the guard is alive, the handler
+/// remains registered with the event; when it is dropped, the handler is
+/// removed via `Event::remove_handler`. This makes dynamic, lifetime-bound
+/// subscriptions - temporary observers, test fixtures - ergonomic instead
+/// of leaking handlers for the lifetime of the event.
+///
+/// Since `Event::emit` requires `&mut self` and runs handlers in threads
+/// scoped to that borrow, a `Subscription` can only be dropped between
+/// `emit` calls - it holds no reference into a running scope, only the
+/// exclusive borrow of the event that the borrow checker already requires.
+/// While the guard is alive, the event is reached through
+/// `Subscription::event_mut`.
+///
+/// # Type Parameters
+///
+/// * `A`: The type of event arguments which are distributed to the handlers.
+/// * `H`: The type of event handlers registered with the event.
+pub struct Subscription<'a, A: Copy + Send, H: EventHandler<A>> {
+    pub(crate) event: &'a mut Event<A, H>,
+    pub(crate) id: HandlerId
+}
+
+impl<'a, A: Copy + Send, H: EventHandler<A>> Subscription<'a, A, H> {
+
+    /// Gets the handler ID of the subscribed handler.
+    pub fn id(&self) -> HandlerId {
+        self.id
+    }
+
+    /// Gets a mutable reference to the event, e.g. to `emit` it while the
+    /// subscribed handler is registered.
+    pub fn event_mut(&mut self) -> &mut Event<A, H> {
+        self.event
+    }
+}
+
+impl<'a, A: Copy + Send, H: EventHandler<A>> Drop for Subscription<'a, A, H> {
+    fn drop(&mut self) {
+        self.event.remove_handler(self.id);
+    }
+}
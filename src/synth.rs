@@ -0,0 +1,18 @@
+//! Contains the `EventSynthesizer` trait, which lets a newly registered
+//! handler immediately catch up on current state instead of only observing
+//! future events.
+
+/// Synthesizes a catch-up batch of event arguments representing the current
+/// state. Used with `Event::add_handler_synth` to let a handler which models
+/// accumulated state, such as a counter or cache, initialize correctly when
+/// it subscribes mid-stream.
+///
+/// # Type Parameters
+///
+/// * `A`: The type of event arguments this synthesizer produces.
+pub trait EventSynthesizer<A> {
+
+    /// Produces the catch-up batch of arguments to deliver, in order, to a
+    /// handler that is newly registered via `Event::add_handler_synth`.
+    fn synthesize(&self) -> Vec<A>;
+}
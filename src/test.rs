@@ -1,30 +1,35 @@
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use super::*;
 
+use crate::bus::EventBus;
+use crate::debounce::DebouncedEvent;
 use crate::handler::StatelessEventHandler;
 use crate::handler::StatefulEventHandler;
+use crate::priority::Priority;
+use crate::synth::EventSynthesizer;
 
 #[test]
 fn empty_emit() {
     // We only test nothing panics
     let mut ev = Event::<i32, StatelessEventHandler<i32>>::new();
-    assert!(ev.emit(5));
+    assert!(matches!(ev.emit(5), EmitResult::AllRan(_)));
 }
 
 fn setup_single_handler_event<'a>() ->
         (Event<i32, StatefulEventHandler<'a, i32, i32>>, HandlerId) {
     let mut ev = Event::<i32, StatefulEventHandler<i32, i32>>::new();
     let handler = StatefulEventHandler::new(|arg, state| *state += arg, 0);
-    let id = ev.add_handler(handler);
+    let id = ev.add_handler(handler, Priority::Normal);
     (ev, id)
 }
 
 #[test]
 fn single_handler_single_event() {
     let (mut ev, id) = setup_single_handler_event();
-    assert!(ev.emit(5));
+    assert!(matches!(ev.emit(5), EmitResult::AllRan(_)));
 
     let new_state = *ev.get_handler(id).unwrap().state();
     assert_eq!(5, new_state);
@@ -33,8 +38,8 @@ fn single_handler_single_event() {
 #[test]
 fn single_handler_multiple_events() {
     let (mut ev, id) = setup_single_handler_event();
-    assert!(ev.emit(5));
-    assert!(ev.emit(3));
+    assert!(matches!(ev.emit(5), EmitResult::AllRan(_)));
+    assert!(matches!(ev.emit(3), EmitResult::AllRan(_)));
 
     let new_state = *ev.get_handler(id).unwrap().state();
     assert_eq!(8, new_state);
@@ -45,11 +50,11 @@ fn multiple_handlers_multiple_events() {
     let mut ev = Event::<i32, StatefulEventHandler<i32, i32>>::new();
     let h1 = StatefulEventHandler::new(|arg, state| *state += arg, 0);
     let h2 = StatefulEventHandler::new(|arg, state| *state *= arg, 1);
-    let id1 = ev.add_handler(h1);
-    let id2 = ev.add_handler(h2);
+    let id1 = ev.add_handler(h1, Priority::Normal);
+    let id2 = ev.add_handler(h2, Priority::Normal);
 
-    assert!(ev.emit(3));
-    assert!(ev.emit(5));
+    assert!(matches!(ev.emit(3), EmitResult::AllRan(_)));
+    assert!(matches!(ev.emit(5), EmitResult::AllRan(_)));
 
     let new_state_1 = *ev.get_handler(id1).unwrap().state();
     let new_state_2 = *ev.get_handler(id2).unwrap().state();
@@ -60,9 +65,9 @@ fn multiple_handlers_multiple_events() {
 #[test]
 fn boxed_handler() {
     // We only test nothing panics and emit(...) terminates.
-    let mut ev = Event::<i32, Box<dyn EventHandler<i32>>>::new();
+    let mut ev = Event::<i32, Box<dyn EventHandler<i32, Output = ()>>>::new();
     let handler = Box::new(StatelessEventHandler::new(|_: i32| { }));
-    ev.add_handler(handler);
+    ev.add_handler(handler, Priority::Normal);
     ev.emit(7);
 }
 
@@ -73,11 +78,13 @@ fn parallel_execution() {
     let handler_count = 32;
 
     for _ in 0..handler_count {
-        ev.add_handler(StatelessEventHandler::new(move |_| thread::sleep(duration)));
+        ev.add_handler(
+            StatelessEventHandler::new(move |_| thread::sleep(duration)),
+            Priority::Normal);
     }
 
     let before = Instant::now();
-    assert!(ev.emit(()));
+    assert!(matches!(ev.emit(()), EmitResult::AllRan(_)));
     let elapsed = before.elapsed();
 
     assert!(elapsed < duration * (handler_count / 2));
@@ -87,9 +94,9 @@ fn parallel_execution() {
 fn awaits() {
     let mut ev = Event::<(), StatefulEventHandler<(), bool>>::new();
     let handler = StatefulEventHandler::new(|_: (), s| *s = true, false);
-    let id = ev.add_handler(handler);
+    let id = ev.add_handler(handler, Priority::Normal);
 
-    assert!(ev.emit(()));
+    assert!(matches!(ev.emit(()), EmitResult::AllRan(_)));
 
     let state = *ev.get_handler(id).unwrap().state();
     assert!(state);
@@ -121,11 +128,372 @@ fn panicking() {
             panic!("(╯°□°）╯︵ ┻━┻");
         }
     }, PanicState::new());
-    let id = ev.add_handler(handler);
+    let id = ev.add_handler(handler, Priority::Normal);
 
-    assert!(!ev.emit(()));
-    assert!(ev.emit(()));
+    assert!(matches!(ev.emit(()), EmitResult::Panicked(_)));
+    assert!(matches!(ev.emit(()), EmitResult::AllRan(_)));
 
     let new_state = ev.get_handler(id).unwrap().state();
     assert!(new_state.calmed);
 }
+
+#[test]
+fn higher_priority_phase_completes_before_lower_priority_phase_starts() {
+    let mut ev = Event::<(), Box<dyn EventHandler<(), Output = ()>>>::new();
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let high_order = Arc::clone(&order);
+    ev.add_handler(
+        Box::new(StatelessEventHandler::new(move |_| {
+            high_order.lock().unwrap().push("high");
+        })),
+        Priority::High);
+
+    let low_order = Arc::clone(&order);
+    ev.add_handler(
+        Box::new(StatelessEventHandler::new(move |_| {
+            low_order.lock().unwrap().push("low");
+        })),
+        Priority::Low);
+
+    assert!(matches!(ev.emit(()), EmitResult::AllRan(_)));
+    assert_eq!(vec!["high", "low"], *order.lock().unwrap());
+}
+
+#[test]
+fn cancellation_skips_lower_priority_phases() {
+    let mut ev = Event::<(), Box<dyn EventHandler<(), Output = ()>>>::new();
+    let ran = Arc::new(Mutex::new(Vec::new()));
+
+    struct Cancelling {
+        ran: Arc<Mutex<Vec<&'static str>>>
+    }
+
+    impl EventHandler<()> for Cancelling {
+        type Output = ();
+
+        fn on_event(&mut self, _arg: ()) {
+            unreachable!("on_event_ctx should be called instead");
+        }
+
+        fn on_event_ctx(&mut self, _arg: (), ctx: &EventContext) {
+            self.ran.lock().unwrap().push("high");
+            ctx.cancel();
+        }
+    }
+
+    ev.add_handler(Box::new(Cancelling { ran: Arc::clone(&ran) }), Priority::High);
+
+    let low_ran = Arc::clone(&ran);
+    ev.add_handler(
+        Box::new(StatelessEventHandler::new(move |_| {
+            low_ran.lock().unwrap().push("low");
+        })),
+        Priority::Low);
+
+    assert!(matches!(ev.emit(()), EmitResult::Cancelled { panicked: false, .. }));
+    assert_eq!(vec!["high"], *ran.lock().unwrap());
+}
+
+#[test]
+fn cancellation_after_a_panic_still_reports_the_panic() {
+    let mut ev = Event::<(), Box<dyn EventHandler<(), Output = ()>>>::new();
+
+    struct Panicking;
+
+    impl EventHandler<()> for Panicking {
+        type Output = ();
+
+        fn on_event(&mut self, _arg: ()) {
+            panic!("(╯°□°）╯︵ ┻━┻");
+        }
+    }
+
+    struct Cancelling;
+
+    impl EventHandler<()> for Cancelling {
+        type Output = ();
+
+        fn on_event(&mut self, _arg: ()) {
+            unreachable!("on_event_ctx should be called instead");
+        }
+
+        fn on_event_ctx(&mut self, _arg: (), ctx: &EventContext) {
+            ctx.cancel();
+        }
+    }
+
+    ev.add_handler(Box::new(Panicking), Priority::High);
+    ev.add_handler(Box::new(Cancelling), Priority::Normal);
+
+    match ev.emit(()) {
+        EmitResult::Cancelled { panicked, .. } => assert!(panicked),
+        other => panic!("expected Cancelled, got {:?}", other)
+    }
+}
+
+#[test]
+fn emit_collects_handler_outputs() {
+    let mut ev = Event::<i32, StatelessEventHandler<i32, i32>>::new();
+    let id1 = ev.add_handler(StatelessEventHandler::new(|arg| arg * 2), Priority::Normal);
+    let id2 = ev.add_handler(StatelessEventHandler::new(|arg| arg * 3), Priority::Normal);
+
+    let results = ev.emit(5).into_results();
+
+    assert_eq!(Some(&10), results.get(&id1));
+    assert_eq!(Some(&15), results.get(&id2));
+}
+
+struct CurrentCount(i32);
+
+impl EventSynthesizer<i32> for CurrentCount {
+    fn synthesize(&self) -> Vec<i32> {
+        vec![self.0]
+    }
+}
+
+#[test]
+fn add_handler_synth_delivers_catch_up_batch_before_registration() {
+    let mut ev = Event::<i32, StatefulEventHandler<i32, i32>>::new();
+    ev.add_handler(StatefulEventHandler::new(|arg, state| *state += arg, 0), Priority::Normal);
+    assert!(matches!(ev.emit(3), EmitResult::AllRan(_)));
+
+    let synth = CurrentCount(3);
+    let id = ev.add_handler_synth(
+        StatefulEventHandler::new(|arg, state| *state += arg, 0),
+        Priority::Normal,
+        &synth);
+
+    assert_eq!(3, *ev.get_handler(id).unwrap().state());
+
+    assert!(matches!(ev.emit(2), EmitResult::AllRan(_)));
+    assert_eq!(5, *ev.get_handler(id).unwrap().state());
+}
+
+#[test]
+fn emit_with_timeout_reports_handlers_still_running_at_deadline() {
+    let mut ev = Event::<(), StatelessEventHandler<()>>::new();
+
+    let fast_id = ev.add_handler(StatelessEventHandler::new(|_| { }), Priority::Normal);
+    let slow_id = ev.add_handler(
+        StatelessEventHandler::new(|_| thread::sleep(Duration::from_millis(200))),
+        Priority::Normal);
+
+    let result = ev.emit_with_timeout((), Duration::from_millis(20));
+
+    assert!(result.completed.contains(&fast_id));
+    assert!(result.timed_out.contains(&slow_id));
+    assert!(result.panicked.is_empty());
+}
+
+#[test]
+fn emit_with_timeout_reports_all_handlers_completed_within_deadline() {
+    let mut ev = Event::<(), StatelessEventHandler<()>>::new();
+    let id = ev.add_handler(StatelessEventHandler::new(|_| { }), Priority::Normal);
+
+    let result = ev.emit_with_timeout((), Duration::from_millis(100));
+
+    assert!(result.completed.contains(&id));
+    assert!(result.timed_out.is_empty());
+}
+
+#[test]
+fn emit_with_timeout_returns_as_soon_as_handlers_finish() {
+    let mut ev = Event::<(), StatelessEventHandler<()>>::new();
+    ev.add_handler(StatelessEventHandler::new(|_| { }), Priority::Normal);
+
+    let before = Instant::now();
+    ev.emit_with_timeout((), Duration::from_secs(2));
+    let elapsed = before.elapsed();
+
+    assert!(elapsed < Duration::from_millis(500), "elapsed = {:?}", elapsed);
+}
+
+#[test]
+fn emit_with_timeout_does_not_report_a_fast_panic_as_timed_out() {
+    let mut ev = Event::<(), StatelessEventHandler<()>>::new();
+    let id = ev.add_handler(StatelessEventHandler::new(|_| panic!("boom")), Priority::Normal);
+
+    let result = ev.emit_with_timeout((), Duration::from_millis(200));
+
+    assert!(result.panicked.contains(&id));
+    assert!(!result.timed_out.contains(&id));
+}
+
+#[test]
+fn emit_reduce_folds_handler_outputs() {
+    let mut ev = Event::<i32, StatelessEventHandler<i32, i32>>::new();
+    ev.add_handler(StatelessEventHandler::new(|arg| arg * 2), Priority::Normal);
+    ev.add_handler(StatelessEventHandler::new(|arg| arg * 3), Priority::Normal);
+
+    let sum = ev.emit_reduce(5, 0, |acc, output| acc + output);
+
+    assert_eq!(25, sum);
+}
+
+#[test]
+fn emit_reduce_skips_lower_priority_phases_after_cancellation() {
+    let mut ev = Event::<(), Box<dyn EventHandler<(), Output = i32>>>::new();
+
+    struct Cancelling;
+
+    impl EventHandler<()> for Cancelling {
+        type Output = i32;
+
+        fn on_event(&mut self, _arg: ()) -> i32 {
+            unreachable!("on_event_ctx should be called instead");
+        }
+
+        fn on_event_ctx(&mut self, _arg: (), ctx: &EventContext) -> i32 {
+            ctx.cancel();
+            1
+        }
+    }
+
+    ev.add_handler(Box::new(Cancelling), Priority::High);
+    ev.add_handler(
+        Box::new(StatelessEventHandler::new(|_| 100)),
+        Priority::Low);
+
+    let sum = ev.emit_reduce((), 0, |acc, output| acc + output);
+
+    assert_eq!(1, sum);
+}
+
+#[test]
+fn remove_handler_stops_it_from_being_notified() {
+    let mut ev = Event::<i32, StatefulEventHandler<i32, i32>>::new();
+    let id = ev.add_handler(StatefulEventHandler::new(|arg, state| *state += arg, 0),
+        Priority::Normal);
+
+    assert!(matches!(ev.emit(3), EmitResult::AllRan(_)));
+    let removed = ev.remove_handler(id);
+
+    assert_eq!(3, *removed.unwrap().state());
+    assert!(ev.get_handler(id).is_none());
+    assert!(ev.remove_handler(id).is_none());
+}
+
+#[test]
+fn get_handler_mut_allows_mutating_state_between_emits() {
+    let mut ev = Event::<i32, StatefulEventHandler<i32, i32>>::new();
+    let handler = StatefulEventHandler::new(|arg, state| *state += arg, 0);
+    let id = ev.add_handler(handler, Priority::Normal);
+
+    *ev.get_handler_mut(id).unwrap() = StatefulEventHandler::new(|arg, state| *state *= arg, 2);
+    assert!(matches!(ev.emit(3), EmitResult::AllRan(_)));
+
+    assert_eq!(6, *ev.get_handler(id).unwrap().state());
+}
+
+#[test]
+fn dropping_subscription_removes_the_handler() {
+    let mut ev = Event::<i32, StatefulEventHandler<i32, i32>>::new();
+
+    let id = {
+        let subscription = ev.add_handler_scoped(
+            StatefulEventHandler::new(|arg, state| *state += arg, 0), Priority::Normal);
+        subscription.id()
+    };
+
+    assert!(ev.get_handler(id).is_none());
+    assert!(matches!(ev.emit(3), EmitResult::AllRan(_)));
+}
+
+#[test]
+fn bus_routes_each_argument_type_to_its_own_subscribers() {
+    let mut bus = EventBus::new();
+    let strings = Arc::new(Mutex::new(Vec::new()));
+    let ints = Arc::new(Mutex::new(Vec::new()));
+
+    let strings_ref = Arc::clone(&strings);
+    bus.subscribe(StatelessEventHandler::new(move |arg: &str| {
+        strings_ref.lock().unwrap().push(arg.to_string());
+    }), Priority::Normal);
+
+    let ints_ref = Arc::clone(&ints);
+    bus.subscribe(StatelessEventHandler::new(move |arg: i32| {
+        ints_ref.lock().unwrap().push(arg);
+    }), Priority::Normal);
+
+    bus.post("hello");
+    bus.post(42);
+
+    assert_eq!(vec!["hello".to_string()], *strings.lock().unwrap());
+    assert_eq!(vec![42], *ints.lock().unwrap());
+}
+
+#[test]
+fn bus_post_without_subscribers_is_a_no_op() {
+    let mut bus = EventBus::new();
+    assert!(bus.post(7));
+}
+
+#[test]
+fn bus_post_reports_false_when_a_handler_panics() {
+    let mut bus = EventBus::new();
+    bus.subscribe(StatelessEventHandler::new(|_: ()| panic!("boom")), Priority::Normal);
+
+    assert!(!bus.post(()));
+}
+
+#[test]
+fn debounced_event_coalesces_a_burst_within_the_window() {
+    let mut ev = Event::<i32, StatefulEventHandler<i32, Vec<i32>>>::new();
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let calls_ref = Arc::clone(&calls);
+    ev.add_handler(
+        StatefulEventHandler::new(move |arg, _: &mut Vec<i32>| calls_ref.lock().unwrap().push(arg),
+            Vec::new()),
+        Priority::Normal);
+
+    let debounced = DebouncedEvent::new(ev, Duration::from_millis(50), Duration::from_secs(10));
+    debounced.emit_debounced(1);
+    debounced.emit_debounced(2);
+    debounced.emit_debounced(3);
+    debounced.flush();
+
+    assert_eq!(vec![3], *calls.lock().unwrap());
+}
+
+#[test]
+fn debounced_event_max_wait_fires_under_a_continuous_stream() {
+    let mut ev = Event::<i32, StatefulEventHandler<i32, Vec<i32>>>::new();
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let calls_ref = Arc::clone(&calls);
+    ev.add_handler(
+        StatefulEventHandler::new(move |arg, _: &mut Vec<i32>| calls_ref.lock().unwrap().push(arg),
+            Vec::new()),
+        Priority::Normal);
+
+    let debounced = DebouncedEvent::new(ev, Duration::from_millis(100), Duration::from_millis(200));
+    let deadline = Instant::now() + Duration::from_millis(500);
+
+    for i in 0..100 {
+        debounced.emit_debounced(i);
+        thread::sleep(Duration::from_millis(20));
+
+        if Instant::now() >= deadline {
+            break;
+        }
+    }
+
+    assert!(!calls.lock().unwrap().is_empty());
+}
+
+#[test]
+fn debounced_event_dispatches_pending_argument_on_drop() {
+    let mut ev = Event::<i32, StatefulEventHandler<i32, Vec<i32>>>::new();
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let calls_ref = Arc::clone(&calls);
+    ev.add_handler(
+        StatefulEventHandler::new(move |arg, _: &mut Vec<i32>| calls_ref.lock().unwrap().push(arg),
+            Vec::new()),
+        Priority::Normal);
+
+    let debounced = DebouncedEvent::new(ev, Duration::from_secs(10), Duration::from_secs(10));
+    debounced.emit_debounced(99);
+    drop(debounced);
+
+    assert_eq!(vec![99], *calls.lock().unwrap());
+}
@@ -0,0 +1,24 @@
+//! Contains the result type returned by `Event::emit_with_timeout`.
+
+use std::collections::HashSet;
+
+use crate::id::HandlerId;
+
+/// The result of `Event::emit_with_timeout`, classifying each handler that
+/// was registered at the time of the call relative to the deadline.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct TimeoutEmitResult {
+
+    /// IDs of handlers that had already finished by the time the deadline
+    /// was reached.
+    pub completed: HashSet<HandlerId>,
+
+    /// IDs of handlers that were still running when the deadline was
+    /// reached. `emit_with_timeout` still waits for these to finish, since
+    /// only well-behaved handlers that poll the `EventContext` can bail out
+    /// cooperatively; the deadline does not preempt them.
+    pub timed_out: HashSet<HandlerId>,
+
+    /// IDs of handlers whose execution panicked.
+    pub panicked: HashSet<HandlerId>
+}